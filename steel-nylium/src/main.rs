@@ -1,15 +1,57 @@
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use async_trait::async_trait;
 use nylium::{Nylium, NyliumLogger};
 use nylium_adapter::fields::{FieldOptions, FieldValue};
 use nylium_adapter::{GameRuleKeys, Global, NyliumServer, Player, PlayerMap};
+use steel::supervisor::RestartPolicy;
 use steel::SteelServer;
 use steel_core::command::sender::CommandSender;
 use steel_core::config::STEEL_CONFIG;
-use tokio::sync::Mutex;
+use steel_core::status::{StatusHandler, StatusResponse, StatusSamplePlayer, StatusVersion};
+use steel_core::text::Component;
+use tokio::select;
+use tokio::sync::{broadcast, Mutex};
 use tokio_util::task::TaskTracker;
 
+/// A config or gamerule value changed at runtime.
+///
+/// Subscribe via [`SteelServerNylium::on_config_change`] to react to changes without polling.
+#[derive(Debug, Clone)]
+enum ConfigChange {
+    Config(SteelConfigKeys),
+    GameRule(GameRuleKeys),
+}
+
+/// The maximum number of players listed in a status response's player sample, independent of
+/// `online_players`. Mirrors vanilla's own small sample cap so a full server doesn't send an
+/// unbounded list on every ping.
+const STATUS_SAMPLE_LIMIT: usize = 12;
+
+/// The subset of [`STEEL_CONFIG`] that can be changed at runtime without a restart.
+#[derive(Clone)]
+struct ConfigOverrides {
+    max_players: i64,
+    view_distance: i64,
+    simulation_distance: i64,
+    motd: String,
+    use_favicon: bool,
+    favicon: String,
+}
+
+impl Default for ConfigOverrides {
+    fn default() -> Self {
+        Self {
+            max_players: STEEL_CONFIG.max_players.into(),
+            view_distance: STEEL_CONFIG.view_distance.into(),
+            simulation_distance: STEEL_CONFIG.simulation_distance.into(),
+            motd: STEEL_CONFIG.motd.clone(),
+            use_favicon: STEEL_CONFIG.use_favicon,
+            favicon: STEEL_CONFIG.favicon.clone(),
+        }
+    }
+}
+
 fn main() {
     let logger = NyliumLogger::init();
 
@@ -21,10 +63,15 @@ struct SteelServerNylium {
     server: Arc<Mutex<Option<SteelServer>>>,
     runtime: Arc<tokio::runtime::Runtime>,
     chunk_runtime: Arc<tokio::runtime::Runtime>,
+    config_overrides: Arc<RwLock<ConfigOverrides>>,
+    game_rules: Arc<RwLock<GameRuleValues>>,
+    config_change: broadcast::Sender<ConfigChange>,
 }
 
 impl SteelServerNylium {
     fn new() -> Self {
+        let (config_change, _) = broadcast::channel(16);
+
         Self {
             server: Arc::new(Mutex::new(None)),
             runtime: Arc::new(
@@ -39,8 +86,71 @@ impl SteelServerNylium {
                     .build()
                     .unwrap(),
             ),
+            config_overrides: Arc::new(RwLock::new(ConfigOverrides::default())),
+            game_rules: Arc::new(RwLock::new(GameRuleValues::default())),
+            config_change,
         }
     }
+
+    /// Subscribes to config and gamerule changes made at runtime, e.g. via the admin panel.
+    fn on_config_change(&self) -> broadcast::Receiver<ConfigChange> {
+        self.config_change.subscribe()
+    }
+
+    /// Builds the status-ping handler installed on the running [`SteelServer`].
+    ///
+    /// Overrides the default handler so the MOTD/max-players reflect runtime overrides, and
+    /// echoes the client's own requested protocol back in [`StatusVersion`] so a server that
+    /// accepts a range of protocols doesn't show the "outdated server" overlay. A protocol the
+    /// client claims but that isn't actually in [`steel::SUPPORTED_PROTOCOLS`] falls back to the
+    /// primary supported version instead of being echoed unvalidated.
+    fn status_handler(&self) -> StatusHandler {
+        let this = self.clone();
+
+        Arc::new(move |protocol, _address| {
+            let this = this.clone();
+
+            Box::pin(async move {
+                let overrides = this.config_overrides.read().unwrap().clone();
+                let mut sample = Vec::new();
+                let mut online_players = 0;
+
+                if let Some(ref steel) = *this.server.lock().await {
+                    steel.server.worlds[0]
+                        .players
+                        .iter_async(|_uuid, player| {
+                            online_players += 1;
+                            if sample.len() < STATUS_SAMPLE_LIMIT {
+                                sample.push(StatusSamplePlayer {
+                                    id: player.gameprofile.id,
+                                    name: player.gameprofile.name.clone(),
+                                });
+                            }
+                            false
+                        })
+                        .await;
+                }
+
+                let protocol = if steel::SUPPORTED_PROTOCOLS.iter().any(|(p, _)| *p == protocol) {
+                    protocol
+                } else {
+                    steel::SUPPORTED_PROTOCOLS[0].0
+                };
+
+                StatusResponse {
+                    online_players,
+                    max_players: overrides.max_players as i32,
+                    sample,
+                    description: Component::text(overrides.motd),
+                    favicon: overrides.use_favicon.then_some(overrides.favicon),
+                    version: StatusVersion {
+                        name: steel::SUPPORTED_VERSION_RANGE.to_string(),
+                        protocol,
+                    },
+                }
+            })
+        })
+    }
 }
 
 impl Global for SteelServerNylium {}
@@ -49,8 +159,42 @@ impl Global for SteelServerNylium {}
 impl NyliumServer<SteelConfigKeys, GameRuleKeys> for SteelServerNylium {
     async fn start(&self) {
         let mut steel = SteelServer::new(self.chunk_runtime.clone()).await;
+        steel.server.set_status_handler(self.status_handler());
         let this = self.clone();
 
+        // Supervised (rather than a bare `self.runtime.spawn`) and tied to `steel.cancel_token`
+        // so a stop/start cycle doesn't leak another copy of this listener: `self` outlives any
+        // one `SteelServer`, so an unsupervised task here would never be cancelled.
+        steel
+            .supervisor
+            .spawn_supervised(
+                "config-change-logger",
+                RestartPolicy::Never,
+                steel.cancel_token.clone(),
+                {
+                    let this = this.clone();
+                    let cancel_token = steel.cancel_token.clone();
+                    move || {
+                        let mut changes = this.on_config_change();
+                        let cancel_token = cancel_token.clone();
+                        async move {
+                            loop {
+                                select! {
+                                    () = cancel_token.cancelled() => break,
+                                    change = changes.recv() => {
+                                        match change {
+                                            Ok(change) => log::info!("Runtime config changed: {change:?}"),
+                                            Err(_) => break,
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+            )
+            .await;
+
         self.runtime.spawn(async move {
             let task_tracker = TaskTracker::new();
             steel.start(task_tracker.clone()).await;
@@ -63,7 +207,8 @@ impl NyliumServer<SteelConfigKeys, GameRuleKeys> for SteelServerNylium {
 
     async fn stop(&self) {
         if let Some(ref steel) = *self.server.lock().await {
-            steel.stop();
+            steel.stop().await;
+            steel.supervisor.shutdown_named("config-change-logger").await;
         }
     }
 
@@ -88,13 +233,49 @@ impl NyliumServer<SteelConfigKeys, GameRuleKeys> for SteelServerNylium {
         players
     }
 
+    /// Runs a console command, optionally on behalf of a player.
+    ///
+    /// A command prefixed with `@<player name>` (e.g. `@Notch gamemode creative`) is dispatched
+    /// as that player instead of the console, and the player gets an action-bar acknowledgement
+    /// once the dispatcher has run it.
     async fn run_command(&self, command: &str) {
         if let Some(ref steel) = *self.server.lock().await {
-            steel.server.command_dispatcher.read().handle_command(
-                CommandSender::Console,
-                command.to_string(),
-                &steel.server,
-            );
+            let (target_name, command) = match command.split_once(' ') {
+                Some((token, rest)) if token.starts_with('@') => {
+                    (Some(&token[1..]), rest.to_string())
+                }
+                _ => (None, command.to_string()),
+            };
+
+            let mut target = None;
+            if let Some(name) = target_name {
+                steel.server.worlds[0]
+                    .players
+                    .iter_async(|_uuid, player| {
+                        if player.gameprofile.name == name {
+                            target = Some(player.clone());
+                        }
+                        false
+                    })
+                    .await;
+            }
+
+            let sender = match target {
+                Some(ref player) => CommandSender::Player(player.gameprofile.id),
+                None => CommandSender::Console,
+            };
+
+            steel
+                .server
+                .command_dispatcher
+                .read()
+                .handle_command(sender, command.clone(), &steel.server);
+
+            if let Some(player) = target {
+                player
+                    .send_system_message(Component::text(format!("Ran: {command}")), true)
+                    .await;
+            }
         }
     }
 
@@ -135,31 +316,100 @@ impl NyliumServer<SteelConfigKeys, GameRuleKeys> for SteelServerNylium {
                 "Enforce Secure Chat",
                 "enforce_secure_chat",
             ),
+            FieldOptions::new_number(
+                SteelConfigKeys::MaxPendingHandshakes,
+                "Max Pending Handshakes",
+                Some(1),
+                Some(10_000),
+            ),
+            FieldOptions::new_number(
+                SteelConfigKeys::ConnectionsPerSecondPerIp,
+                "Connections/sec per IP",
+                Some(1),
+                Some(1_000),
+            ),
+            FieldOptions::new_number(
+                SteelConfigKeys::HandshakeTimeoutSecs,
+                "Handshake Timeout (s)",
+                Some(1),
+                Some(120),
+            ),
         ])
     }
 
     fn get_config_value(&self, key: SteelConfigKeys) -> FieldValue {
+        let overrides = self.config_overrides.read().unwrap();
+
         match key {
             SteelConfigKeys::ServerPort => FieldValue::Number(STEEL_CONFIG.server_port.into()),
             SteelConfigKeys::Seed => FieldValue::String(STEEL_CONFIG.seed.clone()),
-            SteelConfigKeys::MaxPlayers => FieldValue::Number(STEEL_CONFIG.max_players.into()),
-            SteelConfigKeys::ViewDistance => FieldValue::Number(STEEL_CONFIG.view_distance.into()),
+            SteelConfigKeys::MaxPlayers => FieldValue::Number(overrides.max_players),
+            SteelConfigKeys::ViewDistance => FieldValue::Number(overrides.view_distance),
             SteelConfigKeys::SimulationDistance => {
-                FieldValue::Number(STEEL_CONFIG.simulation_distance.into())
+                FieldValue::Number(overrides.simulation_distance)
             }
             SteelConfigKeys::OnlineMode => FieldValue::Boolean(STEEL_CONFIG.online_mode),
             SteelConfigKeys::Encryption => FieldValue::Boolean(STEEL_CONFIG.encryption),
-            SteelConfigKeys::Motd => FieldValue::String(STEEL_CONFIG.motd.clone()),
-            SteelConfigKeys::UseFavicon => FieldValue::Boolean(STEEL_CONFIG.use_favicon),
-            SteelConfigKeys::Favicon => FieldValue::String(STEEL_CONFIG.favicon.clone()),
+            SteelConfigKeys::Motd => FieldValue::String(overrides.motd.clone()),
+            SteelConfigKeys::UseFavicon => FieldValue::Boolean(overrides.use_favicon),
+            SteelConfigKeys::Favicon => FieldValue::String(overrides.favicon.clone()),
             SteelConfigKeys::EnforceSecureChat => {
                 FieldValue::Boolean(STEEL_CONFIG.enforce_secure_chat)
             }
+            SteelConfigKeys::MaxPendingHandshakes => {
+                FieldValue::Number(STEEL_CONFIG.max_pending_handshakes as i64)
+            }
+            SteelConfigKeys::ConnectionsPerSecondPerIp => {
+                FieldValue::Number(STEEL_CONFIG.connections_per_second_per_ip as i64)
+            }
+            SteelConfigKeys::HandshakeTimeoutSecs => {
+                FieldValue::Number(STEEL_CONFIG.handshake_timeout_secs as i64)
+            }
         }
     }
 
-    fn set_config_value(&self, _key: SteelConfigKeys, _value: FieldValue) {
-        // TODO: Allow settings to be changed at runtime
+    fn set_config_value(&self, key: SteelConfigKeys, value: FieldValue) {
+        {
+            let mut overrides = self.config_overrides.write().unwrap();
+            match (key, value) {
+                (SteelConfigKeys::MaxPlayers, FieldValue::Number(n)) => overrides.max_players = n,
+                (SteelConfigKeys::ViewDistance, FieldValue::Number(n)) => {
+                    overrides.view_distance = n;
+                }
+                (SteelConfigKeys::SimulationDistance, FieldValue::Number(n)) => {
+                    overrides.simulation_distance = n;
+                }
+                (SteelConfigKeys::Motd, FieldValue::String(s)) => overrides.motd = s,
+                (SteelConfigKeys::UseFavicon, FieldValue::Boolean(b)) => overrides.use_favicon = b,
+                (SteelConfigKeys::Favicon, FieldValue::String(s)) => overrides.favicon = s,
+                // The rest require a restart to take effect; ignore runtime writes to them.
+                _ => return,
+            }
+        }
+
+        let _ = self.config_change.send(ConfigChange::Config(key));
+
+        let this = self.clone();
+        self.runtime.spawn(async move {
+            if let Some(ref steel) = *this.server.lock().await {
+                let overrides = this.config_overrides.read().unwrap().clone();
+                match key {
+                    SteelConfigKeys::ViewDistance | SteelConfigKeys::SimulationDistance => {
+                        steel
+                            .server
+                            .set_view_distances(overrides.view_distance, overrides.simulation_distance)
+                            .await;
+                    }
+                    SteelConfigKeys::Motd | SteelConfigKeys::UseFavicon | SteelConfigKeys::Favicon => {
+                        steel.server.refresh_status_response().await;
+                    }
+                    SteelConfigKeys::MaxPlayers => {
+                        steel.server.set_max_players(overrides.max_players).await;
+                    }
+                    _ => {}
+                }
+            }
+        });
     }
 
     fn get_gamerules(&self) -> Box<[FieldOptions<GameRuleKeys>]> {
@@ -167,63 +417,444 @@ impl NyliumServer<SteelConfigKeys, GameRuleKeys> for SteelServerNylium {
     }
 
     fn get_gamerule_value(&self, key: GameRuleKeys) -> FieldValue {
+        self.game_rules.read().unwrap().get(key)
+    }
+
+    fn set_gamerule_value(&self, key: GameRuleKeys, value: FieldValue) {
+        if !self.game_rules.write().unwrap().set(key, value) {
+            // Mismatched value type for the key; the cache write was rejected, so don't
+            // broadcast or propagate a gamerule that never actually changed.
+            return;
+        }
+        let _ = self.config_change.send(ConfigChange::GameRule(key));
+
+        let this = self.clone();
+        self.runtime.spawn(async move {
+            if let Some(ref steel) = *this.server.lock().await {
+                // `self.game_rules` only backs the admin panel's view; forward the change to
+                // every world so each world's own gamerules match it.
+                for world in steel.server.worlds.iter() {
+                    world.set_gamerule(key, value).await;
+                }
+            }
+        });
+    }
+}
+
+/// Live values for every gamerule, as reported to and set from the admin panel instead of
+/// hard-coded constants; `set_gamerule_value` also forwards each change to every world.
+///
+/// Defaults match vanilla's.
+#[derive(Clone, Copy)]
+struct GameRuleValues {
+    announce_advancements: bool,
+    block_explosion_drop_decay: bool,
+    command_block_output: bool,
+    command_modification_block_limit: i64,
+    disable_elytra_movement_check: bool,
+    disable_raids: bool,
+    do_daylight_cycle: bool,
+    do_entity_drops: bool,
+    do_fire_tick: bool,
+    do_immediate_respawn: bool,
+    do_insomnia: bool,
+    do_limited_crafting: bool,
+    do_mob_loot: bool,
+    do_mob_spawning: bool,
+    do_patrol_spawning: bool,
+    do_tile_drops: bool,
+    do_trader_spawning: bool,
+    do_vines_spread: bool,
+    do_warden_spawning: bool,
+    do_weather_cycle: bool,
+    drowning_damage: bool,
+    ender_pearls_vanish_on_death: bool,
+    fall_damage: bool,
+    fire_damage: bool,
+    forgive_dead_players: bool,
+    freeze_damage: bool,
+    global_sound_events: bool,
+    keep_inventory: bool,
+    lava_source_conversion: bool,
+    log_admin_commands: bool,
+    max_command_chain_length: i64,
+    max_command_fork_count: i64,
+    max_entity_cramming: i64,
+    mob_explosion_drop_decay: bool,
+    mob_griefing: bool,
+    natural_regeneration: bool,
+    players_nether_portal_creative_delay: i64,
+    players_nether_portal_default_delay: i64,
+    players_sleeping_percentage: i64,
+    projectiles_can_break_blocks: bool,
+    random_tick_speed: i64,
+    reduced_debug_info: bool,
+    send_command_feedback: bool,
+    show_death_messages: bool,
+    snow_accumulation_height: i64,
+    spawn_chunk_radius: i64,
+    spawn_radius: i64,
+    spectators_generate_chunks: bool,
+    tnt_explosion_drop_decay: bool,
+    universal_anger: bool,
+    water_source_conversion: bool,
+}
+
+impl Default for GameRuleValues {
+    fn default() -> Self {
+        Self {
+            announce_advancements: true,
+            block_explosion_drop_decay: true,
+            command_block_output: true,
+            command_modification_block_limit: 32768,
+            disable_elytra_movement_check: false,
+            disable_raids: false,
+            do_daylight_cycle: true,
+            do_entity_drops: true,
+            do_fire_tick: true,
+            do_immediate_respawn: false,
+            do_insomnia: true,
+            do_limited_crafting: false,
+            do_mob_loot: true,
+            do_mob_spawning: true,
+            do_patrol_spawning: true,
+            do_tile_drops: true,
+            do_trader_spawning: true,
+            do_vines_spread: true,
+            do_warden_spawning: true,
+            do_weather_cycle: true,
+            drowning_damage: true,
+            ender_pearls_vanish_on_death: true,
+            fall_damage: true,
+            fire_damage: true,
+            forgive_dead_players: true,
+            freeze_damage: true,
+            global_sound_events: true,
+            keep_inventory: false,
+            lava_source_conversion: false,
+            log_admin_commands: true,
+            max_command_chain_length: 65536,
+            max_command_fork_count: 65536,
+            max_entity_cramming: 24,
+            mob_explosion_drop_decay: true,
+            mob_griefing: true,
+            natural_regeneration: true,
+            players_nether_portal_creative_delay: 1,
+            players_nether_portal_default_delay: 80,
+            players_sleeping_percentage: 100,
+            projectiles_can_break_blocks: true,
+            random_tick_speed: 3,
+            reduced_debug_info: false,
+            send_command_feedback: true,
+            show_death_messages: true,
+            snow_accumulation_height: 1,
+            spawn_chunk_radius: 2,
+            spawn_radius: 10,
+            spectators_generate_chunks: true,
+            tnt_explosion_drop_decay: false,
+            universal_anger: false,
+            water_source_conversion: true,
+        }
+    }
+}
+
+impl GameRuleValues {
+    fn get(&self, key: GameRuleKeys) -> FieldValue {
         match key {
-            GameRuleKeys::AnnounceAdvancements => FieldValue::Boolean(true),
-            GameRuleKeys::BlockExplosionDropDecay => FieldValue::Boolean(true),
-            GameRuleKeys::CommandBlockOutput => FieldValue::Boolean(true),
-            GameRuleKeys::CommandModificationBlockLimit => FieldValue::Number(32768),
-            GameRuleKeys::DisableElytraMovementCheck => FieldValue::Boolean(false),
-            GameRuleKeys::DisableRaids => FieldValue::Boolean(false),
-            GameRuleKeys::DoDaylightCycle => FieldValue::Boolean(true),
-            GameRuleKeys::DoEntityDrops => FieldValue::Boolean(true),
-            GameRuleKeys::DoFireTick => FieldValue::Boolean(true),
-            GameRuleKeys::DoImmediateRespawn => FieldValue::Boolean(false),
-            GameRuleKeys::DoInsomnia => FieldValue::Boolean(true),
-            GameRuleKeys::DoLimitedCrafting => FieldValue::Boolean(false),
-            GameRuleKeys::DoMobLoot => FieldValue::Boolean(true),
-            GameRuleKeys::DoMobSpawning => FieldValue::Boolean(true),
-            GameRuleKeys::DoPatrolSpawning => FieldValue::Boolean(true),
-            GameRuleKeys::DoTileDrops => FieldValue::Boolean(true),
-            GameRuleKeys::DoTraderSpawning => FieldValue::Boolean(true),
-            GameRuleKeys::DoVinesSpread => FieldValue::Boolean(true),
-            GameRuleKeys::DoWardenSpawning => FieldValue::Boolean(true),
-            GameRuleKeys::DoWeatherCycle => FieldValue::Boolean(true),
-            GameRuleKeys::DrowningDamage => FieldValue::Boolean(true),
-            GameRuleKeys::EnderPearlsVanishOnDeath => FieldValue::Boolean(true),
-            GameRuleKeys::FallDamage => FieldValue::Boolean(true),
-            GameRuleKeys::FireDamage => FieldValue::Boolean(true),
-            GameRuleKeys::ForgiveDeadPlayers => FieldValue::Boolean(true),
-            GameRuleKeys::FreezeDamage => FieldValue::Boolean(true),
-            GameRuleKeys::GlobalSoundEvents => FieldValue::Boolean(true),
-            GameRuleKeys::KeepInventory => FieldValue::Boolean(false),
-            GameRuleKeys::LavaSourceConversion => FieldValue::Boolean(false),
-            GameRuleKeys::LogAdminCommands => FieldValue::Boolean(true),
-            GameRuleKeys::MaxCommandChainLength => FieldValue::Number(65536),
-            GameRuleKeys::MaxCommandForkCount => FieldValue::Number(65536),
-            GameRuleKeys::MaxEntityCramming => FieldValue::Number(24),
-            GameRuleKeys::MobExplosionDropDecay => FieldValue::Boolean(true),
-            GameRuleKeys::MobGriefing => FieldValue::Boolean(true),
-            GameRuleKeys::NaturalRegeneration => FieldValue::Boolean(true),
-            GameRuleKeys::PlayersNetherPortalCreativeDelay => FieldValue::Number(1),
-            GameRuleKeys::PlayersNetherPortalDefaultDelay => FieldValue::Number(80),
-            GameRuleKeys::PlayersSleepingPercentage => FieldValue::Number(100),
-            GameRuleKeys::ProjectilesCanBreakBlocks => FieldValue::Boolean(true),
-            GameRuleKeys::RandomTickSpeed => FieldValue::Number(3),
-            GameRuleKeys::ReducedDebugInfo => FieldValue::Boolean(false),
-            GameRuleKeys::SendCommandFeedback => FieldValue::Boolean(true),
-            GameRuleKeys::ShowDeathMessages => FieldValue::Boolean(true),
-            GameRuleKeys::SnowAccumulationHeight => FieldValue::Number(1),
-            GameRuleKeys::SpawnChunkRadius => FieldValue::Number(2),
-            GameRuleKeys::SpawnRadius => FieldValue::Number(10),
-            GameRuleKeys::SpectatorsGenerateChunks => FieldValue::Boolean(true),
-            GameRuleKeys::TntExplosionDropDecay => FieldValue::Boolean(false),
-            GameRuleKeys::UniversalAnger => FieldValue::Boolean(false),
-            GameRuleKeys::WaterSourceConversion => FieldValue::Boolean(true),
+            GameRuleKeys::AnnounceAdvancements => FieldValue::Boolean(self.announce_advancements),
+            GameRuleKeys::BlockExplosionDropDecay => {
+                FieldValue::Boolean(self.block_explosion_drop_decay)
+            }
+            GameRuleKeys::CommandBlockOutput => FieldValue::Boolean(self.command_block_output),
+            GameRuleKeys::CommandModificationBlockLimit => {
+                FieldValue::Number(self.command_modification_block_limit)
+            }
+            GameRuleKeys::DisableElytraMovementCheck => {
+                FieldValue::Boolean(self.disable_elytra_movement_check)
+            }
+            GameRuleKeys::DisableRaids => FieldValue::Boolean(self.disable_raids),
+            GameRuleKeys::DoDaylightCycle => FieldValue::Boolean(self.do_daylight_cycle),
+            GameRuleKeys::DoEntityDrops => FieldValue::Boolean(self.do_entity_drops),
+            GameRuleKeys::DoFireTick => FieldValue::Boolean(self.do_fire_tick),
+            GameRuleKeys::DoImmediateRespawn => FieldValue::Boolean(self.do_immediate_respawn),
+            GameRuleKeys::DoInsomnia => FieldValue::Boolean(self.do_insomnia),
+            GameRuleKeys::DoLimitedCrafting => FieldValue::Boolean(self.do_limited_crafting),
+            GameRuleKeys::DoMobLoot => FieldValue::Boolean(self.do_mob_loot),
+            GameRuleKeys::DoMobSpawning => FieldValue::Boolean(self.do_mob_spawning),
+            GameRuleKeys::DoPatrolSpawning => FieldValue::Boolean(self.do_patrol_spawning),
+            GameRuleKeys::DoTileDrops => FieldValue::Boolean(self.do_tile_drops),
+            GameRuleKeys::DoTraderSpawning => FieldValue::Boolean(self.do_trader_spawning),
+            GameRuleKeys::DoVinesSpread => FieldValue::Boolean(self.do_vines_spread),
+            GameRuleKeys::DoWardenSpawning => FieldValue::Boolean(self.do_warden_spawning),
+            GameRuleKeys::DoWeatherCycle => FieldValue::Boolean(self.do_weather_cycle),
+            GameRuleKeys::DrowningDamage => FieldValue::Boolean(self.drowning_damage),
+            GameRuleKeys::EnderPearlsVanishOnDeath => {
+                FieldValue::Boolean(self.ender_pearls_vanish_on_death)
+            }
+            GameRuleKeys::FallDamage => FieldValue::Boolean(self.fall_damage),
+            GameRuleKeys::FireDamage => FieldValue::Boolean(self.fire_damage),
+            GameRuleKeys::ForgiveDeadPlayers => FieldValue::Boolean(self.forgive_dead_players),
+            GameRuleKeys::FreezeDamage => FieldValue::Boolean(self.freeze_damage),
+            GameRuleKeys::GlobalSoundEvents => FieldValue::Boolean(self.global_sound_events),
+            GameRuleKeys::KeepInventory => FieldValue::Boolean(self.keep_inventory),
+            GameRuleKeys::LavaSourceConversion => FieldValue::Boolean(self.lava_source_conversion),
+            GameRuleKeys::LogAdminCommands => FieldValue::Boolean(self.log_admin_commands),
+            GameRuleKeys::MaxCommandChainLength => {
+                FieldValue::Number(self.max_command_chain_length)
+            }
+            GameRuleKeys::MaxCommandForkCount => FieldValue::Number(self.max_command_fork_count),
+            GameRuleKeys::MaxEntityCramming => FieldValue::Number(self.max_entity_cramming),
+            GameRuleKeys::MobExplosionDropDecay => {
+                FieldValue::Boolean(self.mob_explosion_drop_decay)
+            }
+            GameRuleKeys::MobGriefing => FieldValue::Boolean(self.mob_griefing),
+            GameRuleKeys::NaturalRegeneration => FieldValue::Boolean(self.natural_regeneration),
+            GameRuleKeys::PlayersNetherPortalCreativeDelay => {
+                FieldValue::Number(self.players_nether_portal_creative_delay)
+            }
+            GameRuleKeys::PlayersNetherPortalDefaultDelay => {
+                FieldValue::Number(self.players_nether_portal_default_delay)
+            }
+            GameRuleKeys::PlayersSleepingPercentage => {
+                FieldValue::Number(self.players_sleeping_percentage)
+            }
+            GameRuleKeys::ProjectilesCanBreakBlocks => {
+                FieldValue::Boolean(self.projectiles_can_break_blocks)
+            }
+            GameRuleKeys::RandomTickSpeed => FieldValue::Number(self.random_tick_speed),
+            GameRuleKeys::ReducedDebugInfo => FieldValue::Boolean(self.reduced_debug_info),
+            GameRuleKeys::SendCommandFeedback => FieldValue::Boolean(self.send_command_feedback),
+            GameRuleKeys::ShowDeathMessages => FieldValue::Boolean(self.show_death_messages),
+            GameRuleKeys::SnowAccumulationHeight => {
+                FieldValue::Number(self.snow_accumulation_height)
+            }
+            GameRuleKeys::SpawnChunkRadius => FieldValue::Number(self.spawn_chunk_radius),
+            GameRuleKeys::SpawnRadius => FieldValue::Number(self.spawn_radius),
+            GameRuleKeys::SpectatorsGenerateChunks => {
+                FieldValue::Boolean(self.spectators_generate_chunks)
+            }
+            GameRuleKeys::TntExplosionDropDecay => {
+                FieldValue::Boolean(self.tnt_explosion_drop_decay)
+            }
+            GameRuleKeys::UniversalAnger => FieldValue::Boolean(self.universal_anger),
+            GameRuleKeys::WaterSourceConversion => {
+                FieldValue::Boolean(self.water_source_conversion)
+            }
         }
     }
 
-    fn set_gamerule_value(&self, _key: GameRuleKeys, _value: FieldValue) {
-        // TODO: Allow gamerules to be changed at runtime
+    /// Sets `key` to `value`, returning `false` (and leaving the rule unchanged) if `value`'s
+    /// type doesn't match `key`'s.
+    fn set(&mut self, key: GameRuleKeys, value: FieldValue) -> bool {
+        match (key, value) {
+            (GameRuleKeys::AnnounceAdvancements, FieldValue::Boolean(b)) => {
+                self.announce_advancements = b;
+                true
+            }
+            (GameRuleKeys::BlockExplosionDropDecay, FieldValue::Boolean(b)) => {
+                self.block_explosion_drop_decay = b;
+                true
+            }
+            (GameRuleKeys::CommandBlockOutput, FieldValue::Boolean(b)) => {
+                self.command_block_output = b;
+                true
+            }
+            (GameRuleKeys::CommandModificationBlockLimit, FieldValue::Number(n)) => {
+                self.command_modification_block_limit = n;
+                true
+            }
+            (GameRuleKeys::DisableElytraMovementCheck, FieldValue::Boolean(b)) => {
+                self.disable_elytra_movement_check = b;
+                true
+            }
+            (GameRuleKeys::DisableRaids, FieldValue::Boolean(b)) => {
+                self.disable_raids = b;
+                true
+            }
+            (GameRuleKeys::DoDaylightCycle, FieldValue::Boolean(b)) => {
+                self.do_daylight_cycle = b;
+                true
+            }
+            (GameRuleKeys::DoEntityDrops, FieldValue::Boolean(b)) => {
+                self.do_entity_drops = b;
+                true
+            }
+            (GameRuleKeys::DoFireTick, FieldValue::Boolean(b)) => {
+                self.do_fire_tick = b;
+                true
+            }
+            (GameRuleKeys::DoImmediateRespawn, FieldValue::Boolean(b)) => {
+                self.do_immediate_respawn = b;
+                true
+            }
+            (GameRuleKeys::DoInsomnia, FieldValue::Boolean(b)) => {
+                self.do_insomnia = b;
+                true
+            }
+            (GameRuleKeys::DoLimitedCrafting, FieldValue::Boolean(b)) => {
+                self.do_limited_crafting = b;
+                true
+            }
+            (GameRuleKeys::DoMobLoot, FieldValue::Boolean(b)) => {
+                self.do_mob_loot = b;
+                true
+            }
+            (GameRuleKeys::DoMobSpawning, FieldValue::Boolean(b)) => {
+                self.do_mob_spawning = b;
+                true
+            }
+            (GameRuleKeys::DoPatrolSpawning, FieldValue::Boolean(b)) => {
+                self.do_patrol_spawning = b;
+                true
+            }
+            (GameRuleKeys::DoTileDrops, FieldValue::Boolean(b)) => {
+                self.do_tile_drops = b;
+                true
+            }
+            (GameRuleKeys::DoTraderSpawning, FieldValue::Boolean(b)) => {
+                self.do_trader_spawning = b;
+                true
+            }
+            (GameRuleKeys::DoVinesSpread, FieldValue::Boolean(b)) => {
+                self.do_vines_spread = b;
+                true
+            }
+            (GameRuleKeys::DoWardenSpawning, FieldValue::Boolean(b)) => {
+                self.do_warden_spawning = b;
+                true
+            }
+            (GameRuleKeys::DoWeatherCycle, FieldValue::Boolean(b)) => {
+                self.do_weather_cycle = b;
+                true
+            }
+            (GameRuleKeys::DrowningDamage, FieldValue::Boolean(b)) => {
+                self.drowning_damage = b;
+                true
+            }
+            (GameRuleKeys::EnderPearlsVanishOnDeath, FieldValue::Boolean(b)) => {
+                self.ender_pearls_vanish_on_death = b;
+                true
+            }
+            (GameRuleKeys::FallDamage, FieldValue::Boolean(b)) => {
+                self.fall_damage = b;
+                true
+            }
+            (GameRuleKeys::FireDamage, FieldValue::Boolean(b)) => {
+                self.fire_damage = b;
+                true
+            }
+            (GameRuleKeys::ForgiveDeadPlayers, FieldValue::Boolean(b)) => {
+                self.forgive_dead_players = b;
+                true
+            }
+            (GameRuleKeys::FreezeDamage, FieldValue::Boolean(b)) => {
+                self.freeze_damage = b;
+                true
+            }
+            (GameRuleKeys::GlobalSoundEvents, FieldValue::Boolean(b)) => {
+                self.global_sound_events = b;
+                true
+            }
+            (GameRuleKeys::KeepInventory, FieldValue::Boolean(b)) => {
+                self.keep_inventory = b;
+                true
+            }
+            (GameRuleKeys::LavaSourceConversion, FieldValue::Boolean(b)) => {
+                self.lava_source_conversion = b;
+                true
+            }
+            (GameRuleKeys::LogAdminCommands, FieldValue::Boolean(b)) => {
+                self.log_admin_commands = b;
+                true
+            }
+            (GameRuleKeys::MaxCommandChainLength, FieldValue::Number(n)) => {
+                self.max_command_chain_length = n;
+                true
+            }
+            (GameRuleKeys::MaxCommandForkCount, FieldValue::Number(n)) => {
+                self.max_command_fork_count = n;
+                true
+            }
+            (GameRuleKeys::MaxEntityCramming, FieldValue::Number(n)) => {
+                self.max_entity_cramming = n;
+                true
+            }
+            (GameRuleKeys::MobExplosionDropDecay, FieldValue::Boolean(b)) => {
+                self.mob_explosion_drop_decay = b;
+                true
+            }
+            (GameRuleKeys::MobGriefing, FieldValue::Boolean(b)) => {
+                self.mob_griefing = b;
+                true
+            }
+            (GameRuleKeys::NaturalRegeneration, FieldValue::Boolean(b)) => {
+                self.natural_regeneration = b;
+                true
+            }
+            (GameRuleKeys::PlayersNetherPortalCreativeDelay, FieldValue::Number(n)) => {
+                self.players_nether_portal_creative_delay = n;
+                true
+            }
+            (GameRuleKeys::PlayersNetherPortalDefaultDelay, FieldValue::Number(n)) => {
+                self.players_nether_portal_default_delay = n;
+                true
+            }
+            (GameRuleKeys::PlayersSleepingPercentage, FieldValue::Number(n)) => {
+                self.players_sleeping_percentage = n;
+                true
+            }
+            (GameRuleKeys::ProjectilesCanBreakBlocks, FieldValue::Boolean(b)) => {
+                self.projectiles_can_break_blocks = b;
+                true
+            }
+            (GameRuleKeys::RandomTickSpeed, FieldValue::Number(n)) => {
+                self.random_tick_speed = n;
+                true
+            }
+            (GameRuleKeys::ReducedDebugInfo, FieldValue::Boolean(b)) => {
+                self.reduced_debug_info = b;
+                true
+            }
+            (GameRuleKeys::SendCommandFeedback, FieldValue::Boolean(b)) => {
+                self.send_command_feedback = b;
+                true
+            }
+            (GameRuleKeys::ShowDeathMessages, FieldValue::Boolean(b)) => {
+                self.show_death_messages = b;
+                true
+            }
+            (GameRuleKeys::SnowAccumulationHeight, FieldValue::Number(n)) => {
+                self.snow_accumulation_height = n;
+                true
+            }
+            (GameRuleKeys::SpawnChunkRadius, FieldValue::Number(n)) => {
+                self.spawn_chunk_radius = n;
+                true
+            }
+            (GameRuleKeys::SpawnRadius, FieldValue::Number(n)) => {
+                self.spawn_radius = n;
+                true
+            }
+            (GameRuleKeys::SpectatorsGenerateChunks, FieldValue::Boolean(b)) => {
+                self.spectators_generate_chunks = b;
+                true
+            }
+            (GameRuleKeys::TntExplosionDropDecay, FieldValue::Boolean(b)) => {
+                self.tnt_explosion_drop_decay = b;
+                true
+            }
+            (GameRuleKeys::UniversalAnger, FieldValue::Boolean(b)) => {
+                self.universal_anger = b;
+                true
+            }
+            (GameRuleKeys::WaterSourceConversion, FieldValue::Boolean(b)) => {
+                self.water_source_conversion = b;
+                true
+            }
+            // A mismatched value type for the key is a client-side bug; ignore it rather than
+            // panicking the whole server, but tell the caller nothing actually changed.
+            _ => false,
+        }
     }
 }
 
@@ -240,4 +871,7 @@ enum SteelConfigKeys {
     UseFavicon,
     Favicon,
     EnforceSecureChat,
+    MaxPendingHandshakes,
+    ConnectionsPerSecondPerIp,
+    HandshakeTimeoutSecs,
 }
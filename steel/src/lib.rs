@@ -3,19 +3,67 @@
 //! The main library for the Steel Minecraft server.
 
 use crate::network::JavaTcpClient;
+use crate::supervisor::{RestartPolicy, TaskSupervisor};
+use crate::throttle::ConnectionThrottle;
 use std::{
     net::{Ipv4Addr, SocketAddrV4},
     sync::Arc,
+    time::Duration,
 };
-use steel_core::{config::STEEL_CONFIG, server::Server};
-use tokio::{net::TcpListener, runtime::Runtime, select, spawn};
+use steel_core::{config::STEEL_CONFIG, server::Server, text::Component, world::World};
+use tokio::{net::TcpListener, runtime::Runtime, select, time::timeout};
 use tokio_util::{sync::CancellationToken, task::TaskTracker};
 
 /// The networking module.
 pub mod network;
 
-/// The supported Minecraft version.
-pub const MC_VERSION: &str = "1.21.11";
+/// The supervised background-task runtime.
+pub mod supervisor;
+
+/// The connection-throttling subsystem.
+pub mod throttle;
+
+/// Identifies which packet-codec implementation serializes/deserializes the wire format for a
+/// negotiated protocol version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProtocolCodec {
+    /// 1.21.11
+    V769,
+    /// 1.21.10
+    V768,
+    /// 1.21.9
+    V767,
+}
+
+/// The protocol versions this server is willing to negotiate with, paired with the codec that
+/// handles their wire format, newest first.
+///
+/// The first entry is treated as the "primary" version: it's the one advertised in the status
+/// response and in the "outdated client/server" disconnect when the client's own protocol
+/// isn't in this table.
+pub const SUPPORTED_PROTOCOLS: &[(i32, ProtocolCodec)] = &[
+    (769, ProtocolCodec::V769),
+    (768, ProtocolCodec::V768),
+    (767, ProtocolCodec::V767),
+];
+
+/// A human-readable range describing [`SUPPORTED_PROTOCOLS`], used in the
+/// "outdated client/server" disconnect message.
+pub const SUPPORTED_VERSION_RANGE: &str = "1.21.9 - 1.21.11";
+
+/// Looks up the codec to use for a protocol number already confirmed to be in
+/// [`SUPPORTED_PROTOCOLS`].
+fn codec_for_protocol(protocol: i32) -> ProtocolCodec {
+    SUPPORTED_PROTOCOLS
+        .iter()
+        .find(|(p, _)| *p == protocol)
+        .map(|(_, codec)| *codec)
+        .expect("protocol was already validated against SUPPORTED_PROTOCOLS")
+}
+
+/// The burst of connections a single IP may open at once before the per-IP token bucket
+/// in [`throttle::ConnectionThrottle`] starts throttling it.
+const CONNECTION_BURST_PER_IP: f32 = 5.0;
 
 /// The main server struct.
 pub struct SteelServer {
@@ -25,6 +73,14 @@ pub struct SteelServer {
     pub server: Arc<Server>,
     /// The server's listen address.
     pub bind_address: SocketAddrV4,
+    /// Gates connections before a [`JavaTcpClient`] is created for them.
+    pub throttle: Arc<ConnectionThrottle>,
+    /// Supervises the tick loop and accept loop tasks.
+    pub supervisor: Arc<TaskSupervisor>,
+    /// Cancelled to stop the accept loop specifically, ahead of the rest of shutdown.
+    accept_cancel_token: CancellationToken,
+    /// Tracks per-client tasks so `stop` can drain them before stopping the tick loop.
+    task_tracker: TaskTracker,
 }
 
 impl SteelServer {
@@ -42,6 +98,14 @@ impl SteelServer {
             cancel_token,
             server: Arc::new(server),
             bind_address: SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, STEEL_CONFIG.server_port),
+            throttle: Arc::new(ConnectionThrottle::new(
+                STEEL_CONFIG.max_pending_handshakes,
+                STEEL_CONFIG.connections_per_second_per_ip,
+                CONNECTION_BURST_PER_IP,
+            )),
+            supervisor: Arc::new(TaskSupervisor::new()),
+            accept_cancel_token: CancellationToken::new(),
+            task_tracker: TaskTracker::new(),
         }
     }
 
@@ -49,56 +113,195 @@ impl SteelServer {
     pub async fn start(&mut self, task_tracker: TaskTracker) {
         log::info!("Started Steel Server");
 
-        let server_handle = tokio::spawn({
-            let server = self.server.clone();
-            let cancel_token = self.cancel_token.clone();
+        self.task_tracker = task_tracker.clone();
 
-            async move {
-                server.run(cancel_token.clone()).await;
-            }
-        });
-
-        let tcp_listener = TcpListener::bind(self.bind_address)
-            .await
-            .expect("Failed to bind to server address");
-
-        spawn({
-            let server = self.server.clone();
-            let cancel_token = self.cancel_token.clone();
-            let mut client_id = 0;
-
-            async move {
-                loop {
-                    select! {
-                        () = cancel_token.cancelled() => {
-                            break;
-                        }
-                        accept_result = tcp_listener.accept() => {
-                            let Ok((connection, address)) = accept_result else {
-                                continue;
-                            };
-                            if let Err(e) = connection.set_nodelay(true) {
-                                log::warn!("Failed to set TCP_NODELAY: {e}");
-                            }
-                            let (java_client, sender_recv, net_reader) = JavaTcpClient::new(connection, address, client_id, cancel_token.child_token(), server.clone(), task_tracker.clone());
-                            log::info!("Accepted connection from Java Edition: {address} (id {client_id})");
-                            client_id = client_id.wrapping_add(1);
-
-                            let java_client = Arc::new(java_client);
-                            java_client.start_outgoing_packet_task(sender_recv);
-                            java_client.start_incoming_packet_task(net_reader);
-                            // Java_client won't drop until the incoming and outcoming task close
-                            // So we dont need to care about them here anymore
-                        }
+        self.supervisor
+            .spawn_supervised(
+                "tick-loop",
+                RestartPolicy::OnPanic,
+                self.cancel_token.clone(),
+                {
+                    let server = self.server.clone();
+                    let cancel_token = self.cancel_token.clone();
+                    move || {
+                        let server = server.clone();
+                        let cancel_token = cancel_token.clone();
+                        async move { server.run(cancel_token).await }
                     }
-                }
-                let _ = server_handle.await;
-            }
-        });
+                },
+            )
+            .await;
+
+        let tcp_listener = Arc::new(
+            TcpListener::bind(self.bind_address)
+                .await
+                .expect("Failed to bind to server address"),
+        );
+
+        self.supervisor
+            .spawn_supervised(
+                "accept-loop",
+                RestartPolicy::Never,
+                self.accept_cancel_token.clone(),
+                {
+                    let server = self.server.clone();
+                    let cancel_token = self.cancel_token.clone();
+                    let accept_cancel_token = self.accept_cancel_token.clone();
+                    let throttle = self.throttle.clone();
+                    move || {
+                        accept_loop(
+                            tcp_listener.clone(),
+                            server.clone(),
+                            cancel_token.clone(),
+                            accept_cancel_token.clone(),
+                            throttle.clone(),
+                            task_tracker.clone(),
+                        )
+                    }
+                },
+            )
+            .await;
+    }
+
+    /// Sends a system chat message to a single player.
+    ///
+    /// When `overlay` is `true` the message is routed to the player's action bar
+    /// instead of the chat window, mirroring the 1.19+ `SystemChatMessage` packet.
+    pub async fn send_system_message(&self, player: &JavaTcpClient, component: Component, overlay: bool) {
+        player.send_system_message(component, overlay).await;
+    }
+
+    /// Broadcasts a system chat message to every player currently in `world`.
+    ///
+    /// See [`SteelServer::send_system_message`] for the meaning of `overlay`.
+    pub async fn broadcast_system_message(&self, world: &World, component: Component, overlay: bool) {
+        // `iter_async`'s callback is synchronous, so collect owned handles first and send once
+        // iteration is done, rather than spawning an unsupervised task per player (which would
+        // also swallow any panic from a send).
+        let mut players = Vec::new();
+        world
+            .players
+            .iter_async(|_uuid, player| {
+                players.push(player.clone());
+                false
+            })
+            .await;
+
+        for player in players {
+            player.send_system_message(component.clone(), overlay).await;
+        }
     }
 
     /// Stops the server.
-    pub fn stop(&self) {
+    ///
+    /// Shutdown runs in ordered phases: stop accepting connections, cancel `cancel_token` and
+    /// drain the player tasks, then wait for the tick loop to finish. The tick loop runs on the
+    /// same `cancel_token`, so it is told to stop together with the player tasks, not after
+    /// they have drained. Per-client tasks are children of `cancel_token` (see `accept_loop`),
+    /// so it must be cancelled *before* we wait on `task_tracker`, or an idle client would make
+    /// shutdown hang forever.
+    pub async fn stop(&self) {
+        log::info!("Stopping Steel Server: no longer accepting connections");
+        self.accept_cancel_token.cancel();
+        self.supervisor.shutdown_named("accept-loop").await;
+
+        log::info!("Stopping Steel Server: draining player tasks");
         self.cancel_token.cancel();
+        self.task_tracker.close();
+        self.task_tracker.wait().await;
+
+        log::info!("Stopping Steel Server: waiting for tick loop");
+        self.supervisor.shutdown_named("tick-loop").await;
+
+        log::info!("Steel Server stopped");
+    }
+}
+
+/// Accepts and throttles incoming connections until `accept_cancel_token` fires.
+async fn accept_loop(
+    tcp_listener: Arc<TcpListener>,
+    server: Arc<Server>,
+    cancel_token: CancellationToken,
+    accept_cancel_token: CancellationToken,
+    throttle: Arc<ConnectionThrottle>,
+    task_tracker: TaskTracker,
+) {
+    let handshake_timeout = Duration::from_secs(STEEL_CONFIG.handshake_timeout_secs);
+    let mut client_id = 0;
+
+    loop {
+        select! {
+            () = accept_cancel_token.cancelled() => {
+                break;
+            }
+            accept_result = tcp_listener.accept() => {
+                let Ok((mut connection, address)) = accept_result else {
+                    continue;
+                };
+
+                let is_known_player = server.has_known_player(address.ip());
+                if is_known_player {
+                    throttle.bypass(address.ip());
+                } else if !throttle.try_acquire_ip(address.ip()).await {
+                    log::debug!("Dropping connection from {address}: per-IP rate limit exceeded");
+                    continue;
+                }
+                let Some(pending_permit) = throttle.try_acquire_pending() else {
+                    log::debug!("Dropping connection from {address}: too many pending handshakes");
+                    continue;
+                };
+
+                if let Err(e) = connection.set_nodelay(true) {
+                    log::warn!("Failed to set TCP_NODELAY: {e}");
+                }
+
+                let server = server.clone();
+                let cancel_token = cancel_token.child_token();
+                let task_tracker_clone = task_tracker.clone();
+                let id = client_id;
+                client_id = client_id.wrapping_add(1);
+
+                task_tracker.spawn(async move {
+                    // `negotiate_protocol` only knows about bare protocol numbers; the codec
+                    // each one maps to is looked up separately below via `codec_for_protocol`.
+                    let supported_protocols: Vec<i32> = SUPPORTED_PROTOCOLS.iter().map(|(p, _)| *p).collect();
+
+                    // Holds `pending_permit` for the duration of the handshake; it's dropped
+                    // once this task returns, whether that's because the client reached the
+                    // Play state or because it timed out.
+                    let handshake = timeout(handshake_timeout, network::negotiate_protocol(&mut connection, &supported_protocols)).await;
+
+                    let protocol = match handshake {
+                        Ok(Ok(protocol)) => protocol,
+                        Ok(Err(network::HandshakeError::UnsupportedProtocol(client_protocol))) => {
+                            log::debug!("Rejecting connection from {address}: unsupported protocol {client_protocol}");
+                            network::disconnect_outdated(connection, client_protocol, SUPPORTED_PROTOCOLS[0].0, SUPPORTED_VERSION_RANGE).await;
+                            return;
+                        }
+                        Ok(Err(e)) => {
+                            log::debug!("Failed to read handshake from {address}: {e}");
+                            return;
+                        }
+                        Err(_) => {
+                            log::debug!("Closing connection from {address}: handshake timed out after {handshake_timeout:?}");
+                            return;
+                        }
+                    };
+                    let codec = codec_for_protocol(protocol);
+
+                    let (java_client, sender_recv, net_reader) = JavaTcpClient::new(connection, address, id, protocol, codec, cancel_token, server, task_tracker_clone);
+                    log::info!("Accepted connection from Java Edition: {address} (id {id}, protocol {protocol}, codec {codec:?})");
+
+                    let java_client = Arc::new(java_client);
+                    let outgoing = java_client.start_outgoing_packet_task(sender_recv);
+                    let incoming = java_client.start_incoming_packet_task(net_reader);
+
+                    // Held until the client reaches the Play state or disconnects, at which
+                    // point both packet tasks return and the permit drops.
+                    let _ = tokio::join!(outgoing, incoming);
+                    drop(pending_permit);
+                });
+            }
+        }
     }
 }
@@ -0,0 +1,133 @@
+//! A small supervisor for the server's long-running background tasks.
+//!
+//! Plain `tokio::spawn` loses a task's panic the moment nobody awaits its `JoinHandle`, and
+//! gives no way to restart it. [`TaskSupervisor`] gives every long-running task a name and a
+//! restart policy, logs panics instead of swallowing them, and tracks the handles so
+//! [`TaskSupervisor::shutdown`] can wait for every task to actually finish.
+
+use std::{future::Future, sync::Arc, time::Duration};
+use tokio::{
+    sync::Mutex,
+    task::JoinHandle,
+    time::sleep,
+};
+use tokio_util::sync::CancellationToken;
+
+/// What to do when a supervised task's future returns (including via panic).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Don't restart; a panic is only logged.
+    Never,
+    /// Restart only if the task panicked, not if it returned normally.
+    OnPanic,
+    /// Always restart, regardless of how the task ended, with a fixed backoff between tries.
+    Always {
+        /// Delay before respawning.
+        backoff: Duration,
+    },
+}
+
+/// Supervises a set of named background tasks with panic isolation and restart policies.
+pub struct TaskSupervisor {
+    handles: Mutex<Vec<(&'static str, JoinHandle<()>)>>,
+}
+
+impl TaskSupervisor {
+    /// Creates an empty supervisor.
+    pub fn new() -> Self {
+        Self {
+            handles: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Spawns `future` under supervision as `name`, applying `policy` if it ever returns.
+    ///
+    /// `cancel_token` is checked before a restart is attempted, so a shutdown in progress
+    /// doesn't get raced by a respawn.
+    pub async fn spawn_supervised<F, Fut>(
+        self: &Arc<Self>,
+        name: &'static str,
+        policy: RestartPolicy,
+        cancel_token: CancellationToken,
+        mut make_future: F,
+    ) where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(async move {
+            loop {
+                let result = tokio::spawn(make_future()).await;
+
+                if let Err(panic) = result {
+                    log::error!("Task '{name}' panicked: {panic}");
+                } else {
+                    log::debug!("Task '{name}' exited");
+                }
+
+                if cancel_token.is_cancelled() {
+                    break;
+                }
+
+                let should_restart = match (result.is_err(), policy) {
+                    (_, RestartPolicy::Never) => false,
+                    (true, RestartPolicy::OnPanic) => true,
+                    (false, RestartPolicy::OnPanic) => false,
+                    (_, RestartPolicy::Always { .. }) => true,
+                };
+
+                if !should_restart {
+                    break;
+                }
+
+                if let RestartPolicy::Always { backoff } = policy {
+                    sleep(backoff).await;
+
+                    if cancel_token.is_cancelled() {
+                        break;
+                    }
+                }
+
+                log::info!("Restarting task '{name}'");
+            }
+        });
+
+        self.handles.lock().await.push((name, handle));
+    }
+
+    /// Waits for every supervised task to finish, logging any that panicked.
+    ///
+    /// Intended to be called after `cancel_token.cancel()`, once every task has been told to
+    /// stop, as part of an ordered shutdown.
+    pub async fn shutdown(&self) {
+        let handles = self.handles.lock().await.drain(..).collect::<Vec<_>>();
+
+        for (name, handle) in handles {
+            if let Err(panic) = handle.await {
+                log::error!("Task '{name}' panicked during shutdown: {panic}");
+            }
+        }
+    }
+
+    /// Waits only for the tasks registered under `name`, leaving the rest supervised.
+    ///
+    /// Used to drive an ordered, multi-phase shutdown: cancel one task's token, wait for just
+    /// that task here, then move on to the next phase.
+    pub async fn shutdown_named(&self, name: &str) {
+        let mut handles = self.handles.lock().await;
+        let (matching, rest) = handles.drain(..).partition::<Vec<_>, _>(|(n, _)| *n == name);
+        *handles = rest;
+        drop(handles);
+
+        for (name, handle) in matching {
+            if let Err(panic) = handle.await {
+                log::error!("Task '{name}' panicked during shutdown: {panic}");
+            }
+        }
+    }
+}
+
+impl Default for TaskSupervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
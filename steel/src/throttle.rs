@@ -0,0 +1,167 @@
+//! Connection throttling for the accept loop.
+//!
+//! Gates new connections *before* a [`crate::network::JavaTcpClient`] is created, so a
+//! flood of TCP connections can't spin up a full client object per-socket.
+
+use indexmap::IndexMap;
+use std::{
+    net::IpAddr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+/// How long an IP's bucket may sit untouched before [`ConnectionThrottle::evict_stale`] removes
+/// it. A bucket this old is already back at full `burst`, so dropping it loses no state.
+const STALE_BUCKET_TTL: Duration = Duration::from_secs(600);
+
+/// Bounds the number of connections still in the handshake/login phase, and rate-limits
+/// new connections per source IP.
+pub struct ConnectionThrottle {
+    /// Bounds how many connections may be mid-handshake at once.
+    pending_handshakes: Arc<Semaphore>,
+    /// Per-IP token bucket: `(last_refill, tokens_available)`.
+    buckets: Mutex<IndexMap<IpAddr, (Instant, f32)>>,
+    /// New tokens granted to a bucket per second.
+    refill_rate: f32,
+    /// Maximum tokens a bucket can hold (the allowed connection burst).
+    burst: f32,
+}
+
+impl ConnectionThrottle {
+    /// Creates a new throttle.
+    ///
+    /// `max_pending_handshakes` bounds concurrent in-progress handshakes, `refill_rate` is
+    /// the number of new connections per second allowed from a single IP, and `burst` is the
+    /// largest burst of connections a single IP may open before it starts being throttled.
+    pub fn new(max_pending_handshakes: usize, refill_rate: f32, burst: f32) -> Self {
+        Self {
+            pending_handshakes: Arc::new(Semaphore::new(max_pending_handshakes)),
+            buckets: Mutex::new(IndexMap::new()),
+            refill_rate,
+            burst,
+        }
+    }
+
+    /// Attempts to acquire a permit for a connection still in the handshake/login phase.
+    ///
+    /// Returns `None` if the server already has the maximum number of pending handshakes
+    /// in flight; the caller should close the connection in that case.
+    pub fn try_acquire_pending(&self) -> Option<OwnedSemaphorePermit> {
+        self.pending_handshakes.clone().try_acquire_owned().ok()
+    }
+
+    /// Checks and consumes one token from `ip`'s bucket, refilling it based on elapsed time.
+    ///
+    /// Returns `true` if a token was available (the connection may proceed). Already
+    /// authenticated/known players should call [`ConnectionThrottle::bypass`] instead so
+    /// reconnects aren't starved by the bucket.
+    pub async fn try_acquire_ip(&self, ip: IpAddr) -> bool {
+        let mut buckets = self.buckets.lock().await;
+        let now = Instant::now();
+        Self::evict_stale(&mut buckets, now);
+
+        let (last_refill, tokens) = buckets
+            .entry(ip)
+            .or_insert((now, self.burst));
+
+        let elapsed = now.duration_since(*last_refill).as_secs_f32();
+        *tokens = (*tokens + elapsed * self.refill_rate).min(self.burst);
+        *last_refill = now;
+
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Always permits the connection, bypassing the per-IP bucket. Used for reconnects of
+    /// already-authenticated/known players so they aren't starved under load.
+    pub fn bypass(&self, ip: IpAddr) {
+        // Give an IP without a bucket a full one (as `try_acquire_ip` would) and leave an
+        // existing bucket as it is, so a bypass neither grants nor consumes tokens.
+        if let Ok(mut buckets) = self.buckets.try_lock() {
+            let now = Instant::now();
+            Self::evict_stale(&mut buckets, now);
+            buckets.entry(ip).or_insert((now, self.burst));
+        }
+    }
+
+    /// Removes buckets that haven't been touched in [`STALE_BUCKET_TTL`].
+    ///
+    /// A bucket that old has already refilled back to `burst`, so forgetting it changes no
+    /// behavior for that IP's next connection; it just keeps the map from growing forever as
+    /// distinct IPs come and go.
+    fn evict_stale(buckets: &mut IndexMap<IpAddr, (Instant, f32)>, now: Instant) {
+        buckets.retain(|_, (last_refill, _)| now.duration_since(*last_refill) < STALE_BUCKET_TTL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn allows_burst_then_throttles() {
+        let throttle = ConnectionThrottle::new(16, 1.0, 3.0);
+        let ip = IpAddr::from([127, 0, 0, 1]);
+
+        assert!(throttle.try_acquire_ip(ip).await);
+        assert!(throttle.try_acquire_ip(ip).await);
+        assert!(throttle.try_acquire_ip(ip).await);
+        assert!(!throttle.try_acquire_ip(ip).await);
+    }
+
+    #[tokio::test]
+    async fn refills_over_time() {
+        let throttle = ConnectionThrottle::new(16, 1000.0, 1.0);
+        let ip = IpAddr::from([127, 0, 0, 1]);
+
+        assert!(throttle.try_acquire_ip(ip).await);
+        assert!(!throttle.try_acquire_ip(ip).await);
+
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(throttle.try_acquire_ip(ip).await);
+    }
+
+    #[tokio::test]
+    async fn bypass_does_not_consume_a_token() {
+        let throttle = ConnectionThrottle::new(16, 1.0, 1.0);
+        let ip = IpAddr::from([127, 0, 0, 1]);
+
+        throttle.bypass(ip);
+        throttle.bypass(ip);
+        assert!(throttle.try_acquire_ip(ip).await);
+    }
+
+    #[tokio::test]
+    async fn pending_handshakes_are_bounded() {
+        let throttle = ConnectionThrottle::new(1, 1.0, 1.0);
+
+        let permit = throttle.try_acquire_pending();
+        assert!(permit.is_some());
+        assert!(throttle.try_acquire_pending().is_none());
+
+        drop(permit);
+        assert!(throttle.try_acquire_pending().is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stale_buckets_are_evicted() {
+        let throttle = ConnectionThrottle::new(16, 1.0, 1.0);
+        let ip = IpAddr::from([127, 0, 0, 1]);
+
+        assert!(throttle.try_acquire_ip(ip).await);
+        assert_eq!(throttle.buckets.lock().await.len(), 1);
+
+        tokio::time::advance(STALE_BUCKET_TTL + Duration::from_secs(1)).await;
+        throttle.bypass(IpAddr::from([127, 0, 0, 2]));
+
+        let buckets = throttle.buckets.lock().await;
+        assert_eq!(buckets.len(), 1);
+        assert!(buckets.contains_key(&IpAddr::from([127, 0, 0, 2])));
+    }
+}